@@ -0,0 +1,164 @@
+use std::sync::OnceLock;
+use crate::Bitboard;
+use crate::types::Square;
+use crate::position::SQ_CNT;
+
+/// Orthogonal ray directions (file delta, rank delta) for rooks.
+const ROOK_DIRS: [(i32, i32); 4] = [(1, 0), (-1, 0), (0, 1), (0, -1)];
+/// Diagonal ray directions (file delta, rank delta) for bishops.
+const BISHOP_DIRS: [(i32, i32); 4] = [(1, 1), (1, -1), (-1, 1), (-1, -1)];
+
+/// A precomputed magic-bitboard entry for a single square. `attacks` is indexed
+/// by the perfect hash `((occ & mask) * magic) >> shift`, so a runtime lookup is
+/// a mask, a multiply, a shift, and an array read.
+struct Magic {
+    mask: u64,
+    magic: u64,
+    shift: u32,
+    attacks: Vec<u64>,
+}
+
+impl Magic {
+    fn probe(&self, occ: Bitboard) -> Bitboard {
+        let index = (Bitboard(occ.0 & self.mask) * Bitboard(self.magic)).0 >> self.shift;
+        Bitboard(self.attacks[index as usize])
+    }
+}
+
+static ROOK_MAGICS: OnceLock<Vec<Magic>> = OnceLock::new();
+static BISHOP_MAGICS: OnceLock<Vec<Magic>> = OnceLock::new();
+
+/// Rook attack set from `sq` given the occupancy `occ`.
+pub fn rook_attacks(sq: Square, occ: Bitboard) -> Bitboard {
+    ROOK_MAGICS.get_or_init(|| build_table(&ROOK_DIRS))[sq.index()].probe(occ)
+}
+
+/// Bishop attack set from `sq` given the occupancy `occ`.
+pub fn bishop_attacks(sq: Square, occ: Bitboard) -> Bitboard {
+    BISHOP_MAGICS.get_or_init(|| build_table(&BISHOP_DIRS))[sq.index()].probe(occ)
+}
+
+/// Queen attack set, the union of the rook and bishop rays.
+pub fn queen_attacks(sq: Square, occ: Bitboard) -> Bitboard {
+    rook_attacks(sq, occ) | bishop_attacks(sq, occ)
+}
+
+/// The relevant-occupancy mask for `sq`: the rays in the given directions,
+/// stopping one square short of the board edge, since a piece sitting on the
+/// edge can never block travel past it.
+fn relevant_mask(sq: usize, dirs: &[(i32, i32); 4]) -> u64 {
+    let (f0, r0) = (sq as i32 % 8, sq as i32 / 8);
+    let mut mask = 0u64;
+    for &(df, dr) in dirs {
+        let (mut f, mut r) = (f0 + df, r0 + dr);
+        // Walk while the *next* square would still be on the board, which drops
+        // the final edge square from the mask.
+        while (f + df) >= 0 && (f + df) < 8 && (r + dr) >= 0 && (r + dr) < 8 {
+            mask |= 1u64 << (r * 8 + f);
+            f += df;
+            r += dr;
+        }
+    }
+    mask
+}
+
+/// The true attack set from `sq` along the given rays, walking each ray until it
+/// runs off the board or hits a square that is occupied in `occ`.
+fn sliding_attack(sq: usize, occ: u64, dirs: &[(i32, i32); 4]) -> u64 {
+    let (f0, r0) = (sq as i32 % 8, sq as i32 / 8);
+    let mut attacks = 0u64;
+    for &(df, dr) in dirs {
+        let (mut f, mut r) = (f0 + df, r0 + dr);
+        while (0..8).contains(&f) && (0..8).contains(&r) {
+            let bit = 1u64 << (r * 8 + f);
+            attacks |= bit;
+            if occ & bit != 0 {
+                break;
+            }
+            f += df;
+            r += dr;
+        }
+    }
+    attacks
+}
+
+/// Build the per-square magic table for one piece kind by finding, for each
+/// square, a magic that maps every occupancy subset to a collision-free index
+/// (or to an index whose slot already holds the correct attack set).
+fn build_table(dirs: &[(i32, i32); 4]) -> Vec<Magic> {
+    let mut rng = Rng::new(0x246C_CB2D_3B40_1D63);
+    let mut table = Vec::with_capacity(SQ_CNT);
+    for sq in 0..SQ_CNT {
+        let mask = relevant_mask(sq, dirs);
+        let bits = mask.count_ones();
+        let shift = 64 - bits;
+
+        // Enumerate every occupancy subset with the carry-rippler trick and its
+        // reference attack set.
+        let size = 1usize << bits;
+        let mut occupancies = Vec::with_capacity(size);
+        let mut references = Vec::with_capacity(size);
+        let mut sub = 0u64;
+        loop {
+            occupancies.push(sub);
+            references.push(sliding_attack(sq, sub, dirs));
+            sub = sub.wrapping_sub(mask) & mask;
+            if sub == 0 {
+                break;
+            }
+        }
+
+        // Trial magics until one hashes every subset without a fatal collision.
+        let magic = loop {
+            let candidate = rng.sparse_u64();
+            let mut used = vec![u64::MAX; size];
+            let mut ok = true;
+            for (&occ, &reference) in occupancies.iter().zip(references.iter()) {
+                let index = (occ.wrapping_mul(candidate) >> shift) as usize;
+                if used[index] == u64::MAX {
+                    used[index] = reference;
+                } else if used[index] != reference {
+                    ok = false;
+                    break;
+                }
+            }
+            if ok {
+                break candidate;
+            }
+        };
+
+        // Fill the attack table for the accepted magic.
+        let mut attacks = vec![0u64; size];
+        for (&occ, &reference) in occupancies.iter().zip(references.iter()) {
+            let index = (occ.wrapping_mul(magic) >> shift) as usize;
+            attacks[index] = reference;
+        }
+
+        table.push(Magic { mask, magic, shift, attacks });
+    }
+    table
+}
+
+/// A small fixed-seed xorshift64 generator. Using a fixed seed keeps the magic
+/// search deterministic across runs, so the tables are reproducible.
+struct Rng(u64);
+
+impl Rng {
+    fn new(seed: u64) -> Self {
+        Rng(seed)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        let mut x = self.0;
+        x ^= x << 13;
+        x ^= x >> 7;
+        x ^= x << 17;
+        self.0 = x;
+        x
+    }
+
+    /// Low-population-count candidate, which makes good magics far more likely.
+    fn sparse_u64(&mut self) -> u64 {
+        self.next_u64() & self.next_u64() & self.next_u64()
+    }
+}
@@ -1,4 +1,5 @@
-use std::ops::{BitAnd, BitOr, BitOrAssign, Mul};
+use std::ops::{BitAnd, BitOr, BitOrAssign, BitXor, Mul, Not, Shl, Shr, Sub};
+use crate::types::{Rank, Square};
 
 #[derive(Copy, Clone)]
 pub struct Bitboard(pub u64);
@@ -22,7 +23,7 @@ impl Bitboard {
             }
             if x % 8 == 7 {
                 row.push_str("| ");
-                row.push_str(&(x / 8 + 1).to_string());
+                row.push(Rank::new((x / 8) as usize).label());
                 row.push_str("\n+---+---+---+---+---+---+---+---+\n");
                 stack.push(row.clone());
                 row.clear();
@@ -36,6 +37,61 @@ impl Bitboard {
 
         s
     }
+
+    /// Number of set squares (population count).
+    pub fn count(&self) -> u32 {
+        self.0.count_ones()
+    }
+
+    /// The least-significant set square. Undefined on an empty bitboard.
+    pub fn lsb(&self) -> Square {
+        Square(self.0.trailing_zeros() as u8)
+    }
+
+    /// Pop and return the least-significant set square, clearing it. The caller
+    /// must ensure the bitboard is non-empty; `wrapping_sub` keeps the clear from
+    /// panicking in debug builds should that invariant ever be violated.
+    pub fn pop_lsb(&mut self) -> Square {
+        let sq = self.lsb();
+        self.0 &= self.0.wrapping_sub(1);
+        sq
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    /// Whether more than one square is set, without a full popcount.
+    pub fn more_than_one(&self) -> bool {
+        self.0 & self.0.wrapping_sub(1) != 0
+    }
+
+    pub fn contains(&self, s: Square) -> bool {
+        self.0 & s.to_bb().0 != 0
+    }
+}
+
+/// Consuming iterator over the set squares, yielded from least- to
+/// most-significant via repeated `pop_lsb`.
+pub struct BitboardIter(Bitboard);
+
+impl Iterator for BitboardIter {
+    type Item = Square;
+    fn next(&mut self) -> Option<Square> {
+        if self.0.is_empty() {
+            None
+        } else {
+            Some(self.0.pop_lsb())
+        }
+    }
+}
+
+impl IntoIterator for Bitboard {
+    type Item = Square;
+    type IntoIter = BitboardIter;
+    fn into_iter(self) -> BitboardIter {
+        BitboardIter(self)
+    }
 }
 
 impl BitOr for Bitboard {
@@ -47,4 +103,55 @@ impl BitOr for Bitboard {
 
 impl BitOrAssign for Bitboard {
     fn bitor_assign(&mut self, other: Bitboard) -> () { self.0 |= other.0; }
+}
+
+impl Mul for Bitboard {
+    type Output = Bitboard;
+    // Wrapping multiply, as used by the magic-bitboard index hash.
+    fn mul(self, other: Bitboard) -> Bitboard {
+        Bitboard(self.0.wrapping_mul(other.0))
+    }
+}
+
+impl BitAnd for Bitboard {
+    type Output = Bitboard;
+    fn bitand(self, other: Bitboard) -> Bitboard {
+        Bitboard(self.0 & other.0)
+    }
+}
+
+impl BitXor for Bitboard {
+    type Output = Bitboard;
+    fn bitxor(self, other: Bitboard) -> Bitboard {
+        Bitboard(self.0 ^ other.0)
+    }
+}
+
+impl Not for Bitboard {
+    type Output = Bitboard;
+    fn not(self) -> Bitboard {
+        Bitboard(!self.0)
+    }
+}
+
+impl Sub for Bitboard {
+    type Output = Bitboard;
+    // Set difference: the squares in `self` that are not in `other`.
+    fn sub(self, other: Bitboard) -> Bitboard {
+        Bitboard(self.0 & !other.0)
+    }
+}
+
+impl Shl<u32> for Bitboard {
+    type Output = Bitboard;
+    fn shl(self, shift: u32) -> Bitboard {
+        Bitboard(self.0 << shift)
+    }
+}
+
+impl Shr<u32> for Bitboard {
+    type Output = Bitboard;
+    fn shr(self, shift: u32) -> Bitboard {
+        Bitboard(self.0 >> shift)
+    }
 }
\ No newline at end of file
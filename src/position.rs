@@ -1,8 +1,9 @@
 use std::io::BufReader;
 use crate::Bitboard;
 use crate::bitboard::EMPTY;
-use crate::types::{Castling, Color, Piece, PieceType, Square};
+use crate::types::{Castling, Color, File, Move, Piece, PieceType, Rank, Square};
 use crate::types::PieceType::{*};
+use crate::zobrist::zobrist;
 
 /// Board position for new game
 pub const DEFAULT_FEN_STRING: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
@@ -35,7 +36,18 @@ pub static SQ_INDEX_ORDER: [Square; SQ_CNT] = [
     Square::A8, Square::B8, Square::C8, Square::D8, Square::E8, Square::F8, Square::G8, Square::H8,
 ];
 
+/// Irreversible state saved on each `make_move` so `unmake_move` can restore the
+/// prior position exactly, since these fields can't all be recomputed cheaply.
 #[derive(Copy, Clone)]
+struct StateInfo {
+    captured: Piece,
+    castle_rights: Castling,
+    ep_square: Square,
+    rule50_count: u32,
+    key: u64,
+}
+
+#[derive(Clone)]
 pub struct Position {
     // AND the following masks to get the pieces per color
     /// Bitboards for each type of piece regardless of color
@@ -54,6 +66,10 @@ pub struct Position {
     rule50_count: u32,
     /// Number of halfmoves starting at 0.
     game_ply: u32,
+    /// Incrementally-maintained Zobrist hash of the position.
+    key: u64,
+    /// Undo stack of irreversible state, one entry per move made and not yet unmade.
+    history: Vec<StateInfo>,
 }
 
 impl Position {
@@ -98,6 +114,8 @@ impl Position {
             ep_square: Square::NONE,
             rule50_count: 0,
             game_ply: 1,
+            key: 0,
+            history: Vec::new(),
         };
 
         // 1. Piece placement
@@ -141,23 +159,31 @@ impl Position {
         }
 
         // 4. En passant target square
-        for (i, c) in fields[3].chars().enumerate() {
-            if i == 0 {
-                p.ep_square = match c {
-                    '-' => Square::NONE,
-                    'a' => Square::A3,
-                    'b' => Square::B3,
-                    'c' => Square::C3,
-                    'd' => Square::D3,
-                    'e' => Square::E3,
-                    'f' => Square::F3,
-                    'g' => Square::G3,
-                    'h' => Square::H3,
-                    _ => panic!(),
-                };
-            } else if i == 1 {
-                p.ep_square = SQ_INDEX_ORDER[p.ep_square.index() + 24] // 24 squares to move 3 ranks
-            }
+        if fields[3] != "-" {
+            let mut chars = fields[3].chars();
+            let file = match chars.next().unwrap() {
+                'a' => File::A,
+                'b' => File::B,
+                'c' => File::C,
+                'd' => File::D,
+                'e' => File::E,
+                'f' => File::F,
+                'g' => File::G,
+                'h' => File::H,
+                _ => panic!(),
+            };
+            let rank = match chars.next().unwrap() {
+                '1' => Rank::R1,
+                '2' => Rank::R2,
+                '3' => Rank::R3,
+                '4' => Rank::R4,
+                '5' => Rank::R5,
+                '6' => Rank::R6,
+                '7' => Rank::R7,
+                '8' => Rank::R8,
+                _ => panic!(),
+            };
+            p.ep_square = Square::make(file, rank);
         }
 
         // 5. Halfmove clock
@@ -166,6 +192,20 @@ impl Position {
         // 6. Convert fullmove number to game ply
         let fullmove: u32 = fields[5].parse().unwrap();
         p.game_ply = 2 * (fullmove - 1) + if matches!(p.turn, Color::Black) {1} else {0};
+
+        // Fold in the remaining hash components now that every field is known.
+        // Piece placement was already mixed in by put_piece.
+        let z = zobrist();
+        p.key ^= z.castling[p.castle_rights.index()];
+        if matches!(p.turn, Color::Black) {
+            p.key ^= z.side_to_move;
+        }
+        // X-FEN: the en-passant file only matters, and so only affects the hash,
+        // when a pawn is actually in a position to make the capture.
+        if p.ep_square.index() != Square::NONE.index() && p.ep_capture_possible(&p.ep_square) {
+            p.key ^= z.ep_file[p.ep_square.index() % 8];
+        }
+
         p
     }
 
@@ -173,6 +213,265 @@ impl Position {
         self.board[s.index()] = pc;
         self.bbs[pc.type_of() as usize] |= s.to_bb();
         self.bbs_color[pc.color() as usize] |= s.to_bb();
+        self.key ^= zobrist().piece_square[pc.color() as usize][pc.type_of() as usize][s.index()];
+    }
+
+    pub fn remove_piece(&mut self, s: &Square) {
+        let pc = self.board[s.index()];
+        self.key ^= zobrist().piece_square[pc.color() as usize][pc.type_of() as usize][s.index()];
+        self.bbs[pc.type_of() as usize].0 &= !s.to_bb().0;
+        self.bbs_color[pc.color() as usize].0 &= !s.to_bb().0;
+        self.board[s.index()] = Piece::None;
+    }
+
+    /// The incrementally-maintained Zobrist hash of this position.
+    pub fn key(&self) -> u64 {
+        self.key
+    }
+
+    /// Apply `m`, updating the board, bitboards, side to move, castling rights,
+    /// en-passant square, fifty-move clock, ply, and Zobrist key, and pushing the
+    /// irreversible state needed to undo it.
+    pub fn make_move(&mut self, m: Move) {
+        let z = zobrist();
+        let from = m.from();
+        let to = m.to();
+        let flag = m.flag();
+        let pc = self.board[from.index()];
+        let us = self.turn;
+
+        // En-passant captures take the pawn beside the destination, not on it.
+        let cap_sq = if flag == Move::EN_PASSANT {
+            Square::make(to.file(), from.rank())
+        } else {
+            to
+        };
+        let captured = self.board[cap_sq.index()];
+
+        self.history.push(StateInfo {
+            captured,
+            castle_rights: self.castle_rights,
+            ep_square: self.ep_square,
+            rule50_count: self.rule50_count,
+            key: self.key,
+        });
+
+        // Unhash the pre-move castling and en-passant components; they are
+        // re-added from the post-move state below.
+        self.key ^= z.castling[self.castle_rights.index()];
+        if self.ep_square.index() != Square::NONE.index() && self.ep_capture_possible(&self.ep_square) {
+            self.key ^= z.ep_file[self.ep_square.index() % 8];
+        }
+        self.ep_square = Square::NONE;
+
+        // Relocate the pieces.
+        let is_pawn = matches!(pc.type_of(), PieceType::P);
+        self.remove_piece(&from);
+        if !matches!(captured, Piece::None) {
+            self.remove_piece(&cap_sq);
+        }
+        let placed = if flag >= Move::PROMO_N { m.promotion(us) } else { pc };
+        self.put_piece(placed, &to);
+
+        if flag == Move::CASTLE {
+            let (rook_from, rook_to) = Position::castle_rook_squares(&to);
+            let rook = self.board[rook_from.index()];
+            self.remove_piece(&rook_from);
+            self.put_piece(rook, &rook_to);
+        }
+
+        if flag == Move::DOUBLE_PUSH {
+            // The en-passant square sits on the pushed pawn's file, midway
+            // between its start and destination ranks.
+            let rank = Rank::new((from.index() / 8 + to.index() / 8) / 2);
+            self.ep_square = Square::make(from.file(), rank);
+        }
+
+        // Any king or rook move, or a rook being captured, clears rights.
+        self.update_castle_rights(&from);
+        self.update_castle_rights(&to);
+
+        // Flip the side to move and rebuild the variable hash components.
+        self.turn = match self.turn {
+            Color::White => Color::Black,
+            Color::Black => Color::White,
+        };
+        self.key ^= z.side_to_move;
+        self.key ^= z.castling[self.castle_rights.index()];
+        if self.ep_square.index() != Square::NONE.index() && self.ep_capture_possible(&self.ep_square) {
+            self.key ^= z.ep_file[self.ep_square.index() % 8];
+        }
+
+        if is_pawn || !matches!(captured, Piece::None) {
+            self.rule50_count = 0;
+        } else {
+            self.rule50_count += 1;
+        }
+        self.game_ply += 1;
+    }
+
+    /// Revert the most recent `make_move`, restoring the prior position exactly.
+    pub fn unmake_move(&mut self, m: Move) {
+        let from = m.from();
+        let to = m.to();
+        let flag = m.flag();
+        let info = self.history.pop().unwrap();
+
+        // Flip back to the side that made the move.
+        self.turn = match self.turn {
+            Color::White => Color::Black,
+            Color::Black => Color::White,
+        };
+        let us = self.turn;
+
+        if flag == Move::CASTLE {
+            let (rook_from, rook_to) = Position::castle_rook_squares(&to);
+            let rook = self.board[rook_to.index()];
+            self.remove_piece(&rook_to);
+            self.put_piece(rook, &rook_from);
+        }
+
+        // A promotion's origin piece was a pawn; otherwise it's whatever now
+        // stands on the destination square.
+        let moved = if flag >= Move::PROMO_N {
+            Piece::make(us, PieceType::P)
+        } else {
+            self.board[to.index()]
+        };
+        self.remove_piece(&to);
+        self.put_piece(moved, &from);
+
+        if !matches!(info.captured, Piece::None) {
+            let cap_sq = if flag == Move::EN_PASSANT {
+                Square::make(to.file(), from.rank())
+            } else {
+                to
+            };
+            self.put_piece(info.captured, &cap_sq);
+        }
+
+        // The key and the irreversible scalars are restored wholesale; the
+        // piece XORs above left the key dirty, so this overwrite is exact.
+        self.castle_rights = info.castle_rights;
+        self.ep_square = info.ep_square;
+        self.rule50_count = info.rule50_count;
+        self.key = info.key;
+        self.game_ply -= 1;
+    }
+
+    /// The rook's (from, to) squares for a castling move, keyed by the king's
+    /// destination square.
+    fn castle_rook_squares(king_to: &Square) -> (Square, Square) {
+        match king_to.index() {
+            6  => (Square::H1, Square::F1),
+            2  => (Square::A1, Square::D1),
+            62 => (Square::H8, Square::F8),
+            58 => (Square::A8, Square::D8),
+            _  => panic!(),
+        }
+    }
+
+    /// Clear any castling rights invalidated by a king or rook leaving, or a
+    /// rook being captured, on `sq`.
+    fn update_castle_rights(&mut self, sq: &Square) {
+        let mask = match sq.index() {
+            4  => Castling::C_WHITE_K | Castling::C_WHITE_Q,
+            0  => Castling::C_WHITE_Q,
+            7  => Castling::C_WHITE_K,
+            60 => Castling::C_BLACK_K | Castling::C_BLACK_Q,
+            56 => Castling::C_BLACK_Q,
+            63 => Castling::C_BLACK_K,
+            _  => Castling::C_NONE,
+        };
+        self.castle_rights.remove(mask);
+    }
+
+    /// Whether a pawn of the side to move could actually capture on the
+    /// en-passant square `ep`, per the X-FEN convention.
+    fn ep_capture_possible(&self, ep: &Square) -> bool {
+        let file = ep.index() % 8;
+        // The capturing pawn sits one rank behind `ep` on an adjacent file:
+        // behind is "down the board" for White to move, "up" for Black.
+        let (offsets, pawn): ([i32; 2], Piece) = match self.turn {
+            Color::White => ([-9, -7], Piece::WhitePawn),
+            Color::Black => ([7, 9], Piece::BlackPawn),
+        };
+        for off in offsets {
+            let from = ep.index() as i32 + off;
+            if from < 0 || from >= SQ_CNT as i32 {
+                continue;
+            }
+            // Reject file wrap-around (the capture must come from a neighbour).
+            if (from as usize % 8).abs_diff(file) != 1 {
+                continue;
+            }
+            if self.board[from as usize] as u8 == pawn as u8 {
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Serialize the position back to a FEN string, the inverse of `from_fen`.
+    pub fn to_fen(&self) -> String {
+        let mut fen = String::new();
+
+        // 1. Piece placement, rank 8 down to rank 1, with run-length empties.
+        let mut empty = 0;
+        for (i, sq) in SQ_DISPLAY_ORDER.iter().enumerate() {
+            let pc = self.board[sq.index()];
+            if matches!(pc, Piece::None) {
+                empty += 1;
+            } else {
+                if empty > 0 {
+                    fen.push_str(&empty.to_string());
+                    empty = 0;
+                }
+                fen.push(pc.character());
+            }
+            if i % 8 == 7 {
+                if empty > 0 {
+                    fen.push_str(&empty.to_string());
+                    empty = 0;
+                }
+                if i != SQ_CNT - 1 {
+                    fen.push('/');
+                }
+            }
+        }
+
+        // 2. Active color
+        fen.push(' ');
+        fen.push_str(match self.turn {
+            Color::White => "w",
+            Color::Black => "b",
+        });
+
+        // 3. Castling availability, rebuilt from the rights bits.
+        fen.push(' ');
+        let c = self.castle_rights.index();
+        if c == Castling::C_NONE.index() {
+            fen.push('-');
+        } else {
+            if c & Castling::C_WHITE_K.index() != 0 { fen.push('K'); }
+            if c & Castling::C_WHITE_Q.index() != 0 { fen.push('Q'); }
+            if c & Castling::C_BLACK_K.index() != 0 { fen.push('k'); }
+            if c & Castling::C_BLACK_Q.index() != 0 { fen.push('q'); }
+        }
+
+        // 4. En passant target square in algebraic notation, or "-".
+        fen.push(' ');
+        if self.ep_square.index() == Square::NONE.index() {
+            fen.push('-');
+        } else {
+            let idx = self.ep_square.index();
+            fen.push((b'a' + (idx % 8) as u8) as char);
+            fen.push_str(&(idx / 8 + 1).to_string());
+        }
+
+        // 5. Halfmove clock and 6. fullmove number, recovered from the ply.
+        fen.push_str(&format!(" {} {}", self.rule50_count, self.game_ply / 2 + 1));
+        fen
     }
 
     pub fn pretty(&self) -> String {
@@ -195,3 +494,110 @@ impl Position {
         s
     }
 }
+
+impl PartialEq for Position {
+    fn eq(&self, other: &Self) -> bool {
+        self.turn as u8 == other.turn as u8
+            && self.castle_rights.index() == other.castle_rights.index()
+            && self.ep_square.index() == other.ep_square.index()
+            && self.rule50_count == other.rule50_count
+            && self.game_ply == other.game_ply
+            && self.board.iter().zip(other.board.iter()).all(|(a, b)| *a as u8 == *b as u8)
+            && self.bbs.iter().zip(other.bbs.iter()).all(|(a, b)| a.0 == b.0)
+            && self.bbs_color.iter().zip(other.bbs_color.iter()).all(|(a, b)| a.0 == b.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Position, DEFAULT_FEN_STRING};
+    use crate::types::{Move, Square};
+
+    /// make_move followed by unmake_move leaves the position and its hash key
+    /// exactly as they were, across a double push, a capture, castling, an
+    /// en-passant capture, and promotions with and without a capture.
+    #[test]
+    fn make_unmake_round_trips() {
+        let cases = [
+            // Quiet double push from the opening position.
+            (DEFAULT_FEN_STRING, Move::new(Square::E2, Square::E4, Move::DOUBLE_PUSH)),
+            // Pawn capture (exd5), exercising the captured-piece restore.
+            (
+                "rnbqkbnr/ppp1pppp/8/3p4/4P3/8/PPPP1PPP/RNBQKBNR w KQkq d6 0 2",
+                Move::new(Square::E4, Square::D5, Move::QUIET),
+            ),
+            // Kingside castling, exercising the rook relocation and its undo.
+            (
+                "r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1",
+                Move::new(Square::E1, Square::G1, Move::CASTLE),
+            ),
+            // En-passant capture: the taken pawn sits on d5, not the d6 target.
+            (
+                "rnbqkbnr/ppp1pppp/8/3pP3/8/8/PPPP1PPP/RNBQKBNR w KQkq d6 0 3",
+                Move::new(Square::E5, Square::D6, Move::EN_PASSANT),
+            ),
+            // Promotion to a queen with no capture.
+            (
+                "8/P7/8/8/8/8/8/k6K w - - 0 1",
+                Move::new(Square::A7, Square::A8, Move::PROMO_Q),
+            ),
+            // Promotion to a queen while capturing (axb8=Q).
+            (
+                "1r6/P7/8/8/8/8/8/k6K w - - 0 1",
+                Move::new(Square::A7, Square::B8, Move::PROMO_Q),
+            ),
+        ];
+        for (fen, m) in cases {
+            let mut pos = Position::from_fen(fen);
+            let before = pos.clone();
+            let before_key = pos.key();
+            pos.make_move(m);
+            pos.unmake_move(m);
+            assert!(pos == before);
+            assert_eq!(pos.key(), before_key);
+        }
+    }
+
+    /// The incremental key maintained by make_move matches the key a fresh parse
+    /// of the resulting position computes.
+    #[test]
+    fn make_move_key_matches_fresh_parse() {
+        let cases = [
+            (DEFAULT_FEN_STRING, Move::new(Square::E2, Square::E4, Move::DOUBLE_PUSH)),
+            (
+                "r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1",
+                Move::new(Square::E1, Square::G1, Move::CASTLE),
+            ),
+            (
+                "rnbqkbnr/ppp1pppp/8/3pP3/8/8/PPPP1PPP/RNBQKBNR w KQkq d6 0 3",
+                Move::new(Square::E5, Square::D6, Move::EN_PASSANT),
+            ),
+            (
+                "1r6/P7/8/8/8/8/8/k6K w - - 0 1",
+                Move::new(Square::A7, Square::B8, Move::PROMO_Q),
+            ),
+        ];
+        for (fen, m) in cases {
+            let mut pos = Position::from_fen(fen);
+            pos.make_move(m);
+            let fresh = Position::from_fen(&pos.to_fen());
+            assert_eq!(pos.key(), fresh.key());
+        }
+    }
+
+    #[test]
+    fn fen_round_trips() {
+        let fens = [
+            DEFAULT_FEN_STRING,
+            "8/8/8/8/8/8/8/8 w - - 0 1",
+            "r3k2r/8/8/8/8/8/8/R3K2R b KQkq - 5 10",
+            "rnbqkbnr/pp1ppppp/8/2p5/4P3/8/PPPP1PPP/RNBQKBNR w KQkq c6 0 2",
+        ];
+        for fen in fens {
+            let pos = Position::from_fen(fen);
+            assert_eq!(pos.to_fen(), fen);
+            // Re-parsing the emitted FEN yields an equal position.
+            assert!(Position::from_fen(&pos.to_fen()) == pos);
+        }
+    }
+}
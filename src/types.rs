@@ -194,7 +194,143 @@ impl Square {
     }
 
     pub fn to_bb(&self) -> Bitboard {
-        Bitboard(1u64 >> self.0 as u64)
+        Bitboard(1u64 << self.0 as u64)
+    }
+
+    pub fn file(&self) -> File {
+        File::new(self.index() % 8)
+    }
+
+    pub fn rank(&self) -> Rank {
+        Rank::new(self.index() / 8)
+    }
+
+    pub fn make(file: File, rank: Rank) -> Square {
+        Square((rank as u8) * 8 + file as u8)
+    }
+}
+
+/// Board files A through H, ordered from the queenside.
+#[derive(Copy, Clone)]
+pub enum File {
+    A = 0, B, C, D, E, F, G, H,
+}
+
+impl File {
+    /// The number of files on a chessboard.
+    pub const NUM_VARIANTS: usize = 8;
+
+    pub fn new(index: usize) -> File {
+        match index {
+            0 => File::A,
+            1 => File::B,
+            2 => File::C,
+            3 => File::D,
+            4 => File::E,
+            5 => File::F,
+            6 => File::G,
+            7 => File::H,
+            _ => panic!(),
+        }
+    }
+}
+
+/// Board ranks 1 through 8, ordered from White's back rank.
+#[derive(Copy, Clone)]
+pub enum Rank {
+    R1 = 0, R2, R3, R4, R5, R6, R7, R8,
+}
+
+impl Rank {
+    /// The number of ranks on a chessboard.
+    pub const NUM_VARIANTS: usize = 8;
+
+    pub fn new(index: usize) -> Rank {
+        match index {
+            0 => Rank::R1,
+            1 => Rank::R2,
+            2 => Rank::R3,
+            3 => Rank::R4,
+            4 => Rank::R5,
+            5 => Rank::R6,
+            6 => Rank::R7,
+            7 => Rank::R8,
+            _ => panic!(),
+        }
+    }
+
+    /// The rank's display label, '1' through '8'.
+    pub fn label(self) -> char {
+        (b'1' + self as u8) as char
+    }
+}
+
+/// Single-file masks, indexed by `File`. File A is the a-file, `0x0101010101010101`.
+pub static FILES: [Bitboard; File::NUM_VARIANTS] = [
+    Bitboard(0x0101010101010101),
+    Bitboard(0x0202020202020202),
+    Bitboard(0x0404040404040404),
+    Bitboard(0x0808080808080808),
+    Bitboard(0x1010101010101010),
+    Bitboard(0x2020202020202020),
+    Bitboard(0x4040404040404040),
+    Bitboard(0x8080808080808080),
+];
+
+/// Single-rank masks, indexed by `Rank`. Rank 1 is the first rank, `0xFF`.
+pub static RANKS: [Bitboard; Rank::NUM_VARIANTS] = [
+    Bitboard(0x0000_0000_0000_00FF),
+    Bitboard(0x0000_0000_0000_FF00),
+    Bitboard(0x0000_0000_00FF_0000),
+    Bitboard(0x0000_0000_FF00_0000),
+    Bitboard(0x0000_00FF_0000_0000),
+    Bitboard(0x0000_FF00_0000_0000),
+    Bitboard(0x00FF_0000_0000_0000),
+    Bitboard(0xFF00_0000_0000_0000),
+];
+
+/// A compact move, packed into a `u16`: bits 0-5 are the from-square, bits 6-11
+/// the to-square, and bits 12-15 a flag distinguishing quiet moves, double pawn
+/// pushes, en-passant captures, castling, and the four promotion pieces.
+#[derive(Copy, Clone)]
+pub struct Move(pub u16);
+
+impl Move {
+    pub const QUIET: u16 = 0;
+    pub const DOUBLE_PUSH: u16 = 1;
+    pub const EN_PASSANT: u16 = 2;
+    pub const CASTLE: u16 = 3;
+    pub const PROMO_N: u16 = 4;
+    pub const PROMO_B: u16 = 5;
+    pub const PROMO_R: u16 = 6;
+    pub const PROMO_Q: u16 = 7;
+
+    pub fn new(from: Square, to: Square, flag: u16) -> Move {
+        Move(from.0 as u16 | (to.0 as u16) << 6 | flag << 12)
+    }
+
+    pub fn from(self) -> Square {
+        Square((self.0 & 0x3F) as u8)
+    }
+
+    pub fn to(self) -> Square {
+        Square(((self.0 >> 6) & 0x3F) as u8)
+    }
+
+    pub fn flag(self) -> u16 {
+        (self.0 >> 12) & 0xF
+    }
+
+    /// The piece a promotion move produces; only meaningful for promotion flags.
+    pub fn promotion(self, color: Color) -> Piece {
+        let piece_type = match self.flag() {
+            Move::PROMO_N => PieceType::N,
+            Move::PROMO_B => PieceType::B,
+            Move::PROMO_R => PieceType::R,
+            Move::PROMO_Q => PieceType::Q,
+            _ => PieceType::None,
+        };
+        Piece::make(color, piece_type)
     }
 }
 
@@ -210,6 +346,16 @@ impl Castling {
     pub const C_BLACK_Q: Castling = Castling(0b0001);
     pub const C_ALL: Castling = Castling(0b1111);
     pub const C_NONE: Castling = Castling(0b0000);
+
+    /// The raw 4-bit rights, usable as an index into a 16-entry table.
+    pub fn index(self) -> usize {
+        self.0 as usize
+    }
+
+    /// Clear the rights held by `other`.
+    pub fn remove(&mut self, other: Castling) {
+        self.0 &= !other.0;
+    }
 }
 
 impl BitOr for Castling {
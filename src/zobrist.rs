@@ -0,0 +1,65 @@
+use std::sync::OnceLock;
+use crate::position::{COLOR_CNT, PIECE_TYPE_CNT, SQ_CNT};
+
+/// Randomly-initialized Zobrist keys. Every independent component of a position
+/// (a piece on a square, the castling rights, the en-passant file, and the side
+/// to move) owns an independent key; the position hash is their XOR, which makes
+/// it cheap to keep up to date as pieces move.
+pub struct Zobrist {
+    pub piece_square: [[[u64; SQ_CNT]; PIECE_TYPE_CNT]; COLOR_CNT],
+    pub castling: [u64; 16],
+    pub ep_file: [u64; 8],
+    pub side_to_move: u64,
+}
+
+static ZOBRIST: OnceLock<Zobrist> = OnceLock::new();
+
+/// The process-wide Zobrist tables. Seeded from a fixed PRNG so the keys are
+/// reproducible across runs.
+pub fn zobrist() -> &'static Zobrist {
+    ZOBRIST.get_or_init(init)
+}
+
+fn init() -> Zobrist {
+    let mut rng = Rng::new(0x9D39_247E_33776D41);
+    let mut z = Zobrist {
+        piece_square: [[[0u64; SQ_CNT]; PIECE_TYPE_CNT]; COLOR_CNT],
+        castling: [0u64; 16],
+        ep_file: [0u64; 8],
+        side_to_move: 0,
+    };
+    for color in z.piece_square.iter_mut() {
+        for piece in color.iter_mut() {
+            for sq in piece.iter_mut() {
+                *sq = rng.next_u64();
+            }
+        }
+    }
+    for c in z.castling.iter_mut() {
+        *c = rng.next_u64();
+    }
+    for f in z.ep_file.iter_mut() {
+        *f = rng.next_u64();
+    }
+    z.side_to_move = rng.next_u64();
+    z
+}
+
+/// A small fixed-seed xorshift64 generator, matching the one used to search for
+/// magics, so the whole crate derives its randomness reproducibly.
+struct Rng(u64);
+
+impl Rng {
+    fn new(seed: u64) -> Self {
+        Rng(seed)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        let mut x = self.0;
+        x ^= x << 13;
+        x ^= x >> 7;
+        x ^= x << 17;
+        self.0 = x;
+        x
+    }
+}